@@ -0,0 +1,243 @@
+//! Data types produced by [`crate::Parser`].
+
+use std::collections::HashMap;
+
+use smol_str::SmolStr;
+
+/// Directive tag names recognized while parsing M3U/M3U8 files.
+pub mod directives {
+    pub const EXTM3U: &str = "#EXTM3U";
+    pub const EXTM3U_LEN: usize = EXTM3U.len();
+    pub const EXTINF: &str = "#EXTINF";
+    pub const PLAYLIST: &str = "#PLAYLIST";
+    pub const EXT_X_STREAM_INF: &str = "#EXT-X-STREAM-INF";
+    pub const EXT_X_KEY: &str = "#EXT-X-KEY";
+    pub const EXT_X_SESSION_KEY: &str = "#EXT-X-SESSION-KEY";
+    pub const EXT_X_VERSION: &str = "#EXT-X-VERSION";
+    pub const EXT_X_TARGETDURATION: &str = "#EXT-X-TARGETDURATION";
+    pub const EXT_X_MEDIA_SEQUENCE: &str = "#EXT-X-MEDIA-SEQUENCE";
+    pub const EXT_X_DISCONTINUITY_SEQUENCE: &str = "#EXT-X-DISCONTINUITY-SEQUENCE";
+    pub const EXT_X_ENDLIST: &str = "#EXT-X-ENDLIST";
+    pub const EXT_X_PLAYLIST_TYPE: &str = "#EXT-X-PLAYLIST-TYPE";
+    pub const EXT_X_INDEPENDENT_SEGMENTS: &str = "#EXT-X-INDEPENDENT-SEGMENTS";
+    pub const EXT_X_BYTERANGE: &str = "#EXT-X-BYTERANGE";
+    pub const EXT_X_MAP: &str = "#EXT-X-MAP";
+}
+
+/// A parsed M3U/M3U8 playlist.
+///
+/// This is the generic, untyped view of a playlist: every media entry seen
+/// during parsing is appended to `medias`, regardless of whether the source
+/// document turns out to be an HLS master playlist or a media playlist. Use
+/// [`crate::Parser::get_playlist_typed`] for a view split by
+/// [`PlaylistKind`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct M3uPlaylist {
+    pub attributes: HashMap<SmolStr, SmolStr>,
+    pub title: Option<SmolStr>,
+    pub medias: Vec<M3uMedia>,
+    pub variant_streams: Vec<VariantStream>,
+    /// Decryption keys declared at the playlist level via
+    /// `#EXT-X-SESSION-KEY`, as opposed to `#EXT-X-KEY` which applies to the
+    /// segments that follow it (see [`M3uMedia::key`]).
+    pub session_keys: Vec<DecryptionKey>,
+    /// `#EXT-X-VERSION`.
+    pub version: Option<usize>,
+    /// `#EXT-X-TARGETDURATION`, in seconds. Per RFC 8216 this is a
+    /// decimal-integer, so a value such as `6.0` is left unset rather than
+    /// accepted.
+    pub target_duration: Option<usize>,
+    /// `#EXT-X-MEDIA-SEQUENCE`.
+    pub media_sequence: Option<u64>,
+    /// `#EXT-X-DISCONTINUITY-SEQUENCE`.
+    pub discontinuity_sequence: Option<u64>,
+    /// `#EXT-X-ENDLIST`.
+    pub end_list: bool,
+    /// `#EXT-X-PLAYLIST-TYPE`.
+    pub playlist_type: Option<PlaylistType>,
+    /// `#EXT-X-INDEPENDENT-SEGMENTS`.
+    pub independent_segments: bool,
+}
+
+/// The value of a `#EXT-X-PLAYLIST-TYPE` directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistType {
+    Vod,
+    Event,
+}
+
+impl PlaylistType {
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        match value {
+            "VOD" => Some(Self::Vod),
+            "EVENT" => Some(Self::Event),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Vod => "VOD",
+            Self::Event => "EVENT",
+        }
+    }
+}
+
+/// A single media entry (segment or channel) within a playlist.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct M3uMedia {
+    pub duration: f64,
+    pub name: Option<SmolStr>,
+    pub attributes: HashMap<SmolStr, SmolStr>,
+    pub extension_data: HashMap<SmolStr, Option<SmolStr>>,
+    pub location: SmolStr,
+    /// The decryption key in effect for this segment, set by the most
+    /// recent `#EXT-X-KEY` directive.
+    pub key: Option<DecryptionKey>,
+    /// `#EXT-X-BYTERANGE`, the sub-range of `location` this segment covers.
+    pub byte_range: Option<ByteRange>,
+    /// `#EXT-X-MAP`, the initialization section needed to parse this
+    /// segment.
+    pub map: Option<InitializationSegment>,
+}
+
+/// A `<length>[@<offset>]` byte range, as used by `#EXT-X-BYTERANGE` and the
+/// `BYTERANGE` attribute of `#EXT-X-MAP`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub length: u64,
+    /// The starting byte offset. `None` if the literal omitted `@offset` and
+    /// no offset could be carried over: a `#EXT-X-BYTERANGE` takes the end of
+    /// the previous segment's byte range when that end is known, while the
+    /// `BYTERANGE` attribute of `#EXT-X-MAP` is never carried over.
+    pub offset: Option<u64>,
+}
+
+/// A `#EXT-X-MAP` initialization segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InitializationSegment {
+    pub uri: SmolStr,
+    pub byte_range: Option<ByteRange>,
+}
+
+/// The encryption method of a `#EXT-X-KEY` / `#EXT-X-SESSION-KEY` directive.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMethod {
+    #[default]
+    None,
+    Aes128,
+    SampleAes,
+}
+
+impl KeyMethod {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "NONE",
+            Self::Aes128 => "AES-128",
+            Self::SampleAes => "SAMPLE-AES",
+        }
+    }
+}
+
+/// Decryption metadata parsed from a `#EXT-X-KEY` / `#EXT-X-SESSION-KEY`
+/// directive.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DecryptionKey {
+    pub method: KeyMethod,
+    pub uri: Option<SmolStr>,
+    pub iv: Option<[u8; 16]>,
+    pub key_format: Option<SmolStr>,
+    pub key_format_versions: Option<Vec<usize>>,
+}
+
+impl DecryptionKey {
+    pub(crate) fn from_attributes(attributes: &HashMap<SmolStr, SmolStr>) -> Self {
+        Self {
+            method: match attributes.get("METHOD").map(SmolStr::as_str) {
+                Some("AES-128") => KeyMethod::Aes128,
+                Some("SAMPLE-AES") => KeyMethod::SampleAes,
+                _ => KeyMethod::None,
+            },
+            uri: attributes.get("URI").cloned(),
+            iv: attributes.get("IV").and_then(|v| parse_iv(v)),
+            key_format: attributes.get("KEYFORMAT").cloned(),
+            key_format_versions: attributes.get("KEYFORMATVERSIONS").map(|v| {
+                v.split('/')
+                    .filter_map(|version| version.parse().ok())
+                    .collect()
+            }),
+        }
+    }
+}
+
+fn parse_iv(value: &str) -> Option<[u8; 16]> {
+    let hex = value.strip_prefix("0x").or_else(|| value.strip_prefix("0X"))?;
+    if hex.len() != 32 {
+        return None;
+    }
+
+    let mut iv = [0u8; 16];
+    for (byte, chunk) in iv.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(chunk).ok()?, 16).ok()?;
+    }
+    Some(iv)
+}
+
+/// A single `#EXT-X-STREAM-INF` entry paired with the URI line that follows
+/// it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VariantStream {
+    pub uri: SmolStr,
+    pub bandwidth: Option<u64>,
+    pub average_bandwidth: Option<u64>,
+    pub codecs: Option<SmolStr>,
+    pub resolution: Option<(u32, u32)>,
+    pub frame_rate: Option<f64>,
+    pub audio: Option<SmolStr>,
+    pub video: Option<SmolStr>,
+}
+
+impl VariantStream {
+    pub(crate) fn from_attributes(uri: SmolStr, attributes: &HashMap<SmolStr, SmolStr>) -> Self {
+        Self {
+            uri,
+            bandwidth: attributes.get("BANDWIDTH").and_then(|v| v.parse().ok()),
+            average_bandwidth: attributes
+                .get("AVERAGE-BANDWIDTH")
+                .and_then(|v| v.parse().ok()),
+            codecs: attributes.get("CODECS").cloned(),
+            resolution: attributes.get("RESOLUTION").and_then(|v| {
+                let (width, height) = v.split_once('x')?;
+                Some((width.parse().ok()?, height.parse().ok()?))
+            }),
+            frame_rate: attributes.get("FRAME-RATE").and_then(|v| v.parse().ok()),
+            audio: attributes.get("AUDIO").cloned(),
+            video: attributes.get("VIDEO").cloned(),
+        }
+    }
+}
+
+/// An HLS master playlist: a list of `VariantStream`s pointing at media
+/// playlists, with no media entries of its own.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MasterPlaylist {
+    pub attributes: HashMap<SmolStr, SmolStr>,
+    pub title: Option<SmolStr>,
+    pub variant_streams: Vec<VariantStream>,
+    pub session_keys: Vec<DecryptionKey>,
+}
+
+/// An HLS media playlist: a list of segments (or, for plain M3U, channels).
+///
+/// This is the same shape as [`M3uPlaylist`]; the alias exists so that
+/// [`PlaylistKind::Media`] reads naturally next to
+/// [`PlaylistKind::Master`].
+pub type MediaPlaylist = M3uPlaylist;
+
+/// The kind of playlist a document turned out to be, decided during
+/// parsing by whether any `#EXT-X-STREAM-INF` directive was seen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlaylistKind {
+    Master(MasterPlaylist),
+    Media(MediaPlaylist),
+}
@@ -0,0 +1,12 @@
+//! A small, dependency-light M3U / M3U8 (HLS) playlist parser.
+
+mod format;
+mod parser;
+mod writer;
+
+pub use format::{
+    ByteRange, DecryptionKey, InitializationSegment, KeyMethod, M3uMedia, M3uPlaylist,
+    MasterPlaylist, MediaPlaylist, PlaylistKind, PlaylistType, VariantStream,
+};
+pub use parser::{ParseError, Parser, ParserOptions};
+pub use writer::{DurationFormat, Writer};
@@ -10,11 +10,18 @@ use lazy_static::lazy_static;
 use regex::Regex;
 use smol_str::SmolStr;
 
-use crate::format::{M3uMedia, M3uPlaylist, directives};
+use crate::format::{
+    ByteRange, DecryptionKey, InitializationSegment, M3uMedia, M3uPlaylist, MasterPlaylist,
+    MediaPlaylist, PlaylistKind, PlaylistType, VariantStream, directives,
+};
 
 lazy_static! {
     /// From `https://github.com/Raiper34/m3u-parser-generator/blob/c8e479161dcc4ec3d5490631fa42a1647741481d/src/m3u-parser.ts#L52` (Modified)
     static ref ATTRIBUTE_REGEX: Regex = Regex::new("([^ ]*?)=\"(.*?)\"").expect("Regular expression error");
+    /// Attribute lists such as `#EXT-X-STREAM-INF`'s, where some values (e.g.
+    /// `BANDWIDTH=1280000`) are bare and others (e.g. `CODECS="avc1.4d401e"`)
+    /// are quoted, and commas only separate attributes outside of quotes.
+    static ref MIXED_ATTRIBUTE_REGEX: Regex = Regex::new(r#"([A-Za-z0-9_-]+)=("[^"]*"|[^,]*)"#).expect("Regular expression error");
 }
 
 fn parse_attributes(input: impl AsRef<str>) -> HashMap<SmolStr, SmolStr> {
@@ -29,6 +36,26 @@ fn parse_attributes(input: impl AsRef<str>) -> HashMap<SmolStr, SmolStr> {
     result
 }
 
+/// Parse a `<length>[@<offset>]` byte range literal, as used by
+/// `#EXT-X-BYTERANGE` and the `BYTERANGE` attribute of `#EXT-X-MAP`.
+fn parse_byte_range_literal(value: &str) -> Option<(u64, Option<u64>)> {
+    let mut parts = value.splitn(2, '@');
+    let length = parts.next()?.parse().ok()?;
+    let offset = parts.next().and_then(|o| o.parse().ok());
+    Some((length, offset))
+}
+
+fn parse_mixed_attributes(input: impl AsRef<str>) -> HashMap<SmolStr, SmolStr> {
+    let mut result = HashMap::new();
+    for capture in MIXED_ATTRIBUTE_REGEX.captures_iter(input.as_ref()) {
+        let key = &capture[1];
+        let value = capture[2].trim_matches('"');
+        result.insert(key.into(), value.into());
+    }
+
+    result
+}
+
 /// A parser to parse M3U/M3U8 file.
 ///
 /// Example:
@@ -48,6 +75,37 @@ pub struct Parser<T: BufRead> {
     buffer: String,
     playlist: M3uPlaylist,
     media: M3uMedia,
+    /// Attributes of a `#EXT-X-STREAM-INF` directive waiting to be paired
+    /// with the URI line that follows it.
+    pending_variant_stream: Option<HashMap<SmolStr, SmolStr>>,
+    /// Set once any `#EXT-X-STREAM-INF` directive has been seen, meaning the
+    /// playlist is an HLS master playlist rather than a media playlist.
+    is_master: bool,
+    /// The decryption key declared by the most recent `#EXT-X-KEY`
+    /// directive, applied to every segment until the next one.
+    current_key: Option<DecryptionKey>,
+    /// The initialization segment declared by the most recent `#EXT-X-MAP`
+    /// directive, applied to every segment until the next one.
+    current_map: Option<InitializationSegment>,
+    /// The end (`offset + length`) of the last resolved `#EXT-X-BYTERANGE`,
+    /// used to carry the offset over when a directive omits `@offset`.
+    last_byte_range_end: Option<u64>,
+    options: ParserOptions,
+    /// A line read ahead (e.g. to check for the `#EXTM3U` header in lenient
+    /// mode) and not yet handed to the caller.
+    pending_line: Option<String>,
+    line_number: usize,
+    warnings: Vec<(usize, ParseError)>,
+}
+
+/// Options controlling [`Parser`] behavior.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ParserOptions {
+    /// When `true`, recoverable errors (a malformed `#EXTINF` duration, a
+    /// missing `#EXTM3U` header) are defaulted and recorded as warnings
+    /// (see [`Parser::take_warnings`]) instead of aborting the parse. This
+    /// mirrors how real-world provider playlists routinely violate the spec.
+    pub lenient: bool,
 }
 
 impl<T: BufRead> Parser<T> {
@@ -64,14 +122,53 @@ impl<T: BufRead> Parser<T> {
     /// http://example.com/A.m3u8"#));
     /// ```
     pub fn new(reader: T) -> Self {
+        Self::with_options(reader, ParserOptions::default())
+    }
+
+    /// Create a parser that, instead of aborting on the first recoverable
+    /// error, defaults the offending value and records a warning retrievable
+    /// via [`Parser::take_warnings`]. Equivalent to
+    /// `Parser::with_options(reader, ParserOptions { lenient: true })`.
+    ///
+    /// Example:
+    /// ```rust
+    /// use mediastream_rs::Parser;
+    /// use std::io::Cursor;
+    ///
+    /// let mut parser = Parser::new_lenient(Cursor::new("not a playlist"));
+    /// parser.parse().unwrap();
+    /// assert_eq!(parser.take_warnings().len(), 1);
+    /// ```
+    pub fn new_lenient(reader: T) -> Self {
+        Self::with_options(reader, ParserOptions { lenient: true })
+    }
+
+    /// Create a parser from a stream with explicit [`ParserOptions`].
+    pub fn with_options(reader: T, options: ParserOptions) -> Self {
         Self {
             reader,
             buffer: String::new(),
             playlist: M3uPlaylist::default(),
             media: M3uMedia::default(),
+            pending_variant_stream: None,
+            is_master: false,
+            current_key: None,
+            current_map: None,
+            last_byte_range_end: None,
+            options,
+            pending_line: None,
+            line_number: 0,
+            warnings: Vec::new(),
         }
     }
 
+    /// Take the warnings accumulated while parsing in lenient mode, as
+    /// `(line number, error)` pairs. Returns an empty `Vec` outside of
+    /// lenient mode.
+    pub fn take_warnings(&mut self) -> Vec<(usize, ParseError)> {
+        std::mem::take(&mut self.warnings)
+    }
+
     /// Parse the content from the stream until EOF, and return the error if occurred
     ///
     /// Example:
@@ -89,12 +186,26 @@ impl<T: BufRead> Parser<T> {
         self.parse_m3u_header()?;
 
         while let Some(line) = self.next_line()? {
+            let line_number = self.line_number;
             if line.starts_with('#') {
                 // directive
-                self.parse_directive(line)?;
+                if let Err(e) = self.parse_directive(line) {
+                    if self.options.lenient {
+                        self.warnings.push((line_number, e));
+                    } else {
+                        return Err(e);
+                    }
+                }
+            } else if let Some(attributes) = self.pending_variant_stream.take() {
+                // the URI immediately following a #EXT-X-STREAM-INF directive
+                self.playlist
+                    .variant_streams
+                    .push(VariantStream::from_attributes(line.into(), &attributes));
             } else {
                 // media
                 self.media.location = SmolStr::new(line);
+                self.media.key = self.current_key.clone();
+                self.media.map = self.current_map.clone();
                 let mut media = M3uMedia::default();
                 swap(&mut self.media, &mut media);
                 self.playlist.medias.push(media);
@@ -124,6 +235,46 @@ impl<T: BufRead> Parser<T> {
         result
     }
 
+    /// Get the parsed playlist as a [`PlaylistKind`], split into a
+    /// [`MasterPlaylist`] or [`MediaPlaylist`] depending on whether any
+    /// `#EXT-X-STREAM-INF` directive was seen, and you can continue the next
+    /// parsing.
+    ///
+    /// Example:
+    /// ```rust
+    /// use mediastream_rs::{Parser, PlaylistKind};
+    /// use std::io::Cursor;
+    ///
+    /// let mut parser = Parser::new(Cursor::new(r#"
+    /// #EXTM3U
+    /// #EXT-X-STREAM-INF:BANDWIDTH=1280000,RESOLUTION=640x360
+    /// low.m3u8"#));
+    /// parser.parse().unwrap();
+    /// match parser.get_playlist_typed() {
+    ///     PlaylistKind::Master(master) => assert_eq!(master.variant_streams.len(), 1),
+    ///     PlaylistKind::Media(_) => panic!("expected a master playlist"),
+    /// }
+    /// ```
+    pub fn get_playlist_typed(&mut self) -> PlaylistKind {
+        let is_master = self.is_master;
+        self.is_master = false;
+        let playlist = self.get_playlist();
+
+        if is_master {
+            PlaylistKind::Master(MasterPlaylist {
+                attributes: playlist.attributes,
+                title: playlist.title,
+                variant_streams: playlist.variant_streams,
+                session_keys: playlist.session_keys,
+            })
+        } else {
+            PlaylistKind::Media(MediaPlaylist {
+                variant_streams: Vec::new(),
+                ..playlist
+            })
+        }
+    }
+
     /// Return the inner reader
     ///
     /// Example:
@@ -143,6 +294,10 @@ impl<T: BufRead> Parser<T> {
     }
 
     fn next_line(&mut self) -> Result<Option<String>, io::Error> {
+        if let Some(line) = self.pending_line.take() {
+            return Ok(Some(line));
+        }
+
         loop {
             self.buffer.clear();
             match self.reader.read_line(&mut self.buffer) {
@@ -150,8 +305,9 @@ impl<T: BufRead> Parser<T> {
                 Ok(_) => {}
                 Err(e) => return Err(e),
             }
+            self.line_number += 1;
 
-            if self.buffer.trim().len() != 0 {
+            if !self.buffer.trim().is_empty() {
                 return Ok(Some(self.buffer.trim().to_owned()));
             }
         }
@@ -161,6 +317,12 @@ impl<T: BufRead> Parser<T> {
         let first_line = self.next_line()?.ok_or(ParseError::UnexpectedEOF)?;
 
         if !first_line.starts_with(directives::EXTM3U) {
+            if self.options.lenient {
+                self.warnings
+                    .push((self.line_number, ParseError::NotAPlaylist));
+                self.pending_line = Some(first_line);
+                return Ok(());
+            }
             return Err(ParseError::NotAPlaylist);
         }
 
@@ -190,7 +352,15 @@ impl<T: BufRead> Parser<T> {
 
         // parse duration
         let duration = splited_duration.next().ok_or(ParseError::MissingDuration)?;
-        self.media.duration = duration.parse().map_err(|_| ParseError::MissingDuration)?;
+        self.media.duration = match duration.parse() {
+            Ok(duration) => duration,
+            Err(_) if self.options.lenient => {
+                self.warnings
+                    .push((self.line_number, ParseError::MissingDuration));
+                0.0
+            }
+            Err(_) => return Err(ParseError::MissingDuration),
+        };
 
         // parse attribute
         if let Some(attributes) = splited_duration.next() {
@@ -200,6 +370,20 @@ impl<T: BufRead> Parser<T> {
         Ok(())
     }
 
+    /// Resolve a `#EXT-X-BYTERANGE`'s offset, carrying it over from the end
+    /// of the previous segment's byte range when the directive omits
+    /// `@offset`, and remember where this range ends for the next one.
+    ///
+    /// `offset + length` is computed with `checked_add` rather than `+`: a
+    /// provider-supplied playlist could pair two individually valid `u64`
+    /// literals whose sum overflows, and that must not panic. An overflow is
+    /// treated the same as a missing offset.
+    fn resolve_byte_range(&mut self, length: u64, offset: Option<u64>) -> ByteRange {
+        let offset = offset.or(self.last_byte_range_end);
+        self.last_byte_range_end = offset.and_then(|offset| offset.checked_add(length));
+        ByteRange { length, offset }
+    }
+
     fn parse_directive(&mut self, line: String) -> Result<(), ParseError> {
         let mut splited_line = line.splitn(2, ':');
         let key = splited_line.next().unwrap().into();
@@ -209,6 +393,43 @@ impl<T: BufRead> Parser<T> {
             self.parse_media_info(value.unwrap_or_default())?;
         } else if key == directives::PLAYLIST {
             self.playlist.title = Some(value.unwrap_or_default());
+        } else if key == directives::EXT_X_STREAM_INF {
+            self.is_master = true;
+            self.pending_variant_stream = Some(parse_mixed_attributes(value.unwrap_or_default()));
+        } else if key == directives::EXT_X_KEY {
+            let attributes = parse_mixed_attributes(value.unwrap_or_default());
+            self.current_key = Some(DecryptionKey::from_attributes(&attributes));
+        } else if key == directives::EXT_X_SESSION_KEY {
+            let attributes = parse_mixed_attributes(value.unwrap_or_default());
+            self.playlist
+                .session_keys
+                .push(DecryptionKey::from_attributes(&attributes));
+        } else if key == directives::EXT_X_VERSION {
+            self.playlist.version = value.and_then(|v| v.parse().ok());
+        } else if key == directives::EXT_X_TARGETDURATION {
+            self.playlist.target_duration = value.and_then(|v| v.parse().ok());
+        } else if key == directives::EXT_X_MEDIA_SEQUENCE {
+            self.playlist.media_sequence = value.and_then(|v| v.parse().ok());
+        } else if key == directives::EXT_X_DISCONTINUITY_SEQUENCE {
+            self.playlist.discontinuity_sequence = value.and_then(|v| v.parse().ok());
+        } else if key == directives::EXT_X_ENDLIST {
+            self.playlist.end_list = true;
+        } else if key == directives::EXT_X_PLAYLIST_TYPE {
+            self.playlist.playlist_type = value.and_then(|v| PlaylistType::parse(&v));
+        } else if key == directives::EXT_X_INDEPENDENT_SEGMENTS {
+            self.playlist.independent_segments = true;
+        } else if key == directives::EXT_X_BYTERANGE {
+            self.media.byte_range = parse_byte_range_literal(&value.unwrap_or_default())
+                .map(|(length, offset)| self.resolve_byte_range(length, offset));
+        } else if key == directives::EXT_X_MAP {
+            let attributes = parse_mixed_attributes(value.unwrap_or_default());
+            self.current_map = attributes.get("URI").map(|uri| InitializationSegment {
+                uri: uri.clone(),
+                byte_range: attributes
+                    .get("BYTERANGE")
+                    .and_then(|v| parse_byte_range_literal(v))
+                    .map(|(length, offset)| ByteRange { length, offset }),
+            });
         } else {
             self.media.extension_data.insert(key, value);
         }
@@ -251,7 +472,7 @@ impl From<io::Error> for ParseError {
 mod tests {
     use std::io::Cursor;
 
-    use crate::{Parser, parser::parse_attributes};
+    use crate::{Parser, PlaylistKind, parser::parse_attributes};
 
     #[test]
     fn test_parse_attributes() {
@@ -280,6 +501,76 @@ mod tests {
         parser.parse().unwrap();
         let result = parser.get_playlist();
         assert_eq!(result.medias.len(), 3);
+        assert_eq!(result.version, Some(6));
+        assert_eq!(result.media_sequence, Some(8885));
+        assert_eq!(result.discontinuity_sequence, Some(0));
+        assert_eq!(result.target_duration, Some(6));
+        assert!(result.independent_segments);
+    }
+
+    #[test]
+    fn test_parse_master_playlist() {
+        let data = r#"
+#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=1280000,AVERAGE-BANDWIDTH=1000000,RESOLUTION=640x360,CODECS="avc1.4d401e,mp4a.40.2",FRAME-RATE=30.000,AUDIO="aac"
+low/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2560000,RESOLUTION=1280x720
+high/index.m3u8"#;
+        let mut parser = Parser::new(Cursor::new(data));
+        parser.parse().unwrap();
+
+        match parser.get_playlist_typed() {
+            PlaylistKind::Master(master) => {
+                assert_eq!(master.variant_streams.len(), 2);
+                let low = &master.variant_streams[0];
+                assert_eq!(low.uri, "low/index.m3u8");
+                assert_eq!(low.bandwidth, Some(1_280_000));
+                assert_eq!(low.average_bandwidth, Some(1_000_000));
+                assert_eq!(low.resolution, Some((640, 360)));
+                assert_eq!(low.codecs.as_deref(), Some("avc1.4d401e,mp4a.40.2"));
+                assert_eq!(low.audio.as_deref(), Some("aac"));
+                assert_eq!(master.variant_streams[1].uri, "high/index.m3u8");
+            }
+            PlaylistKind::Media(_) => panic!("expected a master playlist"),
+        }
+    }
+
+    #[test]
+    fn test_parse_decryption_key() {
+        use crate::KeyMethod;
+
+        let data = r#"
+#EXTM3U
+#EXT-X-KEY:METHOD=AES-128,URI="https://example.com/key.bin",IV=0x9c7db8778570d05c3177c349fd9236aa
+#EXTINF:6,
+seg0.ts
+#EXTINF:6,
+seg1.ts
+#EXT-X-KEY:METHOD=NONE
+#EXTINF:6,
+seg2.ts"#;
+        let mut parser = Parser::new(Cursor::new(data));
+        parser.parse().unwrap();
+        let playlist = parser.get_playlist();
+
+        let key0 = playlist.medias[0].key.as_ref().unwrap();
+        assert_eq!(key0.method, KeyMethod::Aes128);
+        assert_eq!(key0.uri.as_deref(), Some("https://example.com/key.bin"));
+        assert_eq!(
+            key0.iv,
+            Some([
+                0x9c, 0x7d, 0xb8, 0x77, 0x85, 0x70, 0xd0, 0x5c, 0x31, 0x77, 0xc3, 0x49, 0xfd, 0x92,
+                0x36, 0xaa
+            ])
+        );
+        assert_eq!(
+            playlist.medias[1].key.as_ref().unwrap().method,
+            KeyMethod::Aes128
+        );
+        assert_eq!(
+            playlist.medias[2].key.as_ref().unwrap().method,
+            KeyMethod::None
+        );
     }
 
     #[test]
@@ -321,4 +612,142 @@ http://example.com/D.m3u8
             "http://example.com/D.m3u8"
         );
     }
+
+    #[test]
+    fn test_lenient_missing_header() {
+        let data = "#EXTINF:1,A\nhttp://example.com/A.m3u8";
+        let mut parser = Parser::new_lenient(Cursor::new(data));
+        parser.parse().unwrap();
+
+        let result = parser.get_playlist();
+        assert_eq!(result.medias.len(), 1);
+
+        let warnings = parser.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].0, 1);
+    }
+
+    #[test]
+    fn test_lenient_malformed_duration() {
+        let data = "#EXTM3U\n#EXTINF:not-a-number,A\nhttp://example.com/A.m3u8";
+        let mut parser = Parser::new_lenient(Cursor::new(data));
+        parser.parse().unwrap();
+
+        let result = parser.get_playlist();
+        assert_eq!(result.medias[0].duration, 0.0);
+
+        let warnings = parser.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].0, 2);
+    }
+
+    #[test]
+    fn test_strict_mode_still_aborts() {
+        let data = "#EXTINF:1,A\nhttp://example.com/A.m3u8";
+        let mut parser = Parser::new(Cursor::new(data));
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn test_parse_vod_end_list() {
+        use crate::PlaylistType;
+
+        let data = r"
+#EXTM3U
+#EXT-X-PLAYLIST-TYPE:VOD
+#EXTINF:6,
+seg0.ts
+#EXT-X-ENDLIST";
+        let mut parser = Parser::new(Cursor::new(data));
+        parser.parse().unwrap();
+        let result = parser.get_playlist();
+
+        assert_eq!(result.playlist_type, Some(PlaylistType::Vod));
+        assert!(result.end_list);
+    }
+
+    #[test]
+    fn test_byte_range_carry_over_offset() {
+        let data = r#"
+#EXTM3U
+#EXT-X-MAP:URI="init.mp4",BYTERANGE="512@0"
+#EXT-X-BYTERANGE:1000@0
+#EXTINF:6,
+fmp4.mp4
+#EXT-X-BYTERANGE:500
+#EXTINF:6,
+fmp4.mp4
+#EXT-X-BYTERANGE:300
+#EXTINF:6,
+fmp4.mp4"#;
+        let mut parser = Parser::new(Cursor::new(data));
+        parser.parse().unwrap();
+        let result = parser.get_playlist();
+
+        for media in &result.medias {
+            let map = media.map.as_ref().unwrap();
+            assert_eq!(map.uri, "init.mp4");
+            assert_eq!(
+                map.byte_range,
+                Some(crate::ByteRange {
+                    length: 512,
+                    offset: Some(0)
+                })
+            );
+        }
+
+        assert_eq!(
+            result.medias[0].byte_range,
+            Some(crate::ByteRange {
+                length: 1000,
+                offset: Some(0)
+            })
+        );
+        assert_eq!(
+            result.medias[1].byte_range,
+            Some(crate::ByteRange {
+                length: 500,
+                offset: Some(1000)
+            })
+        );
+        assert_eq!(
+            result.medias[2].byte_range,
+            Some(crate::ByteRange {
+                length: 300,
+                offset: Some(1500)
+            })
+        );
+    }
+
+    #[test]
+    fn test_byte_range_offset_overflow_does_not_panic() {
+        let data = r"
+#EXTM3U
+#EXT-X-BYTERANGE:10000000000000000000@10000000000000000000
+#EXTINF:6,
+fmp4.mp4
+#EXT-X-BYTERANGE:100
+#EXTINF:6,
+fmp4.mp4";
+        let mut parser = Parser::new(Cursor::new(data));
+        parser.parse().unwrap();
+        let result = parser.get_playlist();
+
+        assert_eq!(
+            result.medias[0].byte_range,
+            Some(crate::ByteRange {
+                length: 10_000_000_000_000_000_000,
+                offset: Some(10_000_000_000_000_000_000)
+            })
+        );
+        // the overflowing end couldn't be resolved, so the next carry-over
+        // offset is unresolvable too rather than a silently wrapped value.
+        assert_eq!(
+            result.medias[1].byte_range,
+            Some(crate::ByteRange {
+                length: 100,
+                offset: None
+            })
+        );
+    }
 }
@@ -0,0 +1,463 @@
+use std::io::{self, Write};
+
+use crate::format::{
+    ByteRange, DecryptionKey, InitializationSegment, M3uMedia, M3uPlaylist, VariantStream,
+    directives,
+};
+
+/// Render a `<length>[@<offset>]` byte range literal, as used by
+/// `#EXT-X-BYTERANGE` and the `BYTERANGE` attribute of `#EXT-X-MAP`.
+fn format_byte_range_literal(byte_range: &ByteRange) -> String {
+    match byte_range.offset {
+        Some(offset) => format!("{}@{offset}", byte_range.length),
+        None => format!("{}", byte_range.length),
+    }
+}
+
+/// Controls how `#EXTINF` durations are rendered.
+///
+/// Some downstream packagers (e.g. AWS Elemental MediaConvert) reject
+/// playlists whose durations are written as bare integers, so the default
+/// is [`DurationFormat::Fixed`] with six decimal places.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationFormat {
+    /// Write durations with no decimal point (e.g. `6`).
+    Integer,
+    /// Write durations with a fixed number of decimal places (e.g. `6.000000`).
+    Fixed(u8),
+}
+
+impl Default for DurationFormat {
+    fn default() -> Self {
+        Self::Fixed(6)
+    }
+}
+
+/// A writer that serializes a `M3uPlaylist` back into `#EXTM3U` text.
+///
+/// Example:
+/// ```rust
+/// use mediastream_rs::{Parser, Writer};
+/// use std::io::Cursor;
+///
+/// let mut parser = Parser::new(Cursor::new(r#"
+/// #EXTM3U x-tvg-url="test"
+/// #EXTINF:1 tvg-id="a" provider-type="iptv",A
+/// http://example.com/A.m3u8"#));
+/// parser.parse().unwrap();
+/// let playlist = parser.get_playlist();
+///
+/// let mut out = Vec::new();
+/// Writer::new(&mut out).write_playlist(&playlist).unwrap();
+/// ```
+pub struct Writer<W: Write> {
+    writer: W,
+    duration_format: DurationFormat,
+}
+
+impl<W: Write> Writer<W> {
+    /// Create a writer using the default [`DurationFormat`].
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            duration_format: DurationFormat::default(),
+        }
+    }
+
+    /// Create a writer with an explicit [`DurationFormat`].
+    pub fn with_duration_format(writer: W, duration_format: DurationFormat) -> Self {
+        Self {
+            writer,
+            duration_format,
+        }
+    }
+
+    /// Serialize the playlist to the underlying writer.
+    pub fn write_playlist(&mut self, playlist: &M3uPlaylist) -> io::Result<()> {
+        write!(self.writer, "{}", directives::EXTM3U)?;
+        for (key, value) in &playlist.attributes {
+            write!(self.writer, " {key}=\"{value}\"")?;
+        }
+        writeln!(self.writer)?;
+
+        if let Some(version) = playlist.version {
+            writeln!(self.writer, "{}:{version}", directives::EXT_X_VERSION)?;
+        }
+        if let Some(target_duration) = playlist.target_duration {
+            writeln!(
+                self.writer,
+                "{}:{target_duration}",
+                directives::EXT_X_TARGETDURATION
+            )?;
+        }
+        if let Some(media_sequence) = playlist.media_sequence {
+            writeln!(
+                self.writer,
+                "{}:{media_sequence}",
+                directives::EXT_X_MEDIA_SEQUENCE
+            )?;
+        }
+        if let Some(discontinuity_sequence) = playlist.discontinuity_sequence {
+            writeln!(
+                self.writer,
+                "{}:{discontinuity_sequence}",
+                directives::EXT_X_DISCONTINUITY_SEQUENCE
+            )?;
+        }
+        if let Some(playlist_type) = playlist.playlist_type {
+            writeln!(
+                self.writer,
+                "{}:{}",
+                directives::EXT_X_PLAYLIST_TYPE,
+                playlist_type.as_str()
+            )?;
+        }
+        if playlist.independent_segments {
+            writeln!(self.writer, "{}", directives::EXT_X_INDEPENDENT_SEGMENTS)?;
+        }
+
+        if let Some(title) = &playlist.title {
+            writeln!(self.writer, "{}:{}", directives::PLAYLIST, title)?;
+        }
+
+        for key in &playlist.session_keys {
+            self.write_decryption_key(directives::EXT_X_SESSION_KEY, key)?;
+        }
+
+        for variant_stream in &playlist.variant_streams {
+            self.write_variant_stream(variant_stream)?;
+        }
+
+        let mut current_key = None;
+        let mut current_map = None;
+        for media in &playlist.medias {
+            if media.key != current_key {
+                match &media.key {
+                    Some(key) => self.write_decryption_key(directives::EXT_X_KEY, key)?,
+                    None => writeln!(self.writer, "{}:METHOD=NONE", directives::EXT_X_KEY)?,
+                }
+                current_key = media.key.clone();
+            }
+            if media.map != current_map {
+                if let Some(map) = &media.map {
+                    self.write_initialization_segment(map)?;
+                }
+                current_map = media.map.clone();
+            }
+            self.write_media(media)?;
+        }
+
+        if playlist.end_list {
+            writeln!(self.writer, "{}", directives::EXT_X_ENDLIST)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_variant_stream(&mut self, variant_stream: &VariantStream) -> io::Result<()> {
+        write!(self.writer, "{}:", directives::EXT_X_STREAM_INF)?;
+        let mut attributes = Vec::new();
+        if let Some(bandwidth) = variant_stream.bandwidth {
+            attributes.push(format!("BANDWIDTH={bandwidth}"));
+        }
+        if let Some(average_bandwidth) = variant_stream.average_bandwidth {
+            attributes.push(format!("AVERAGE-BANDWIDTH={average_bandwidth}"));
+        }
+        if let Some((width, height)) = variant_stream.resolution {
+            attributes.push(format!("RESOLUTION={width}x{height}"));
+        }
+        if let Some(codecs) = &variant_stream.codecs {
+            attributes.push(format!("CODECS=\"{codecs}\""));
+        }
+        if let Some(frame_rate) = variant_stream.frame_rate {
+            attributes.push(format!("FRAME-RATE={frame_rate}"));
+        }
+        if let Some(audio) = &variant_stream.audio {
+            attributes.push(format!("AUDIO=\"{audio}\""));
+        }
+        if let Some(video) = &variant_stream.video {
+            attributes.push(format!("VIDEO=\"{video}\""));
+        }
+        writeln!(self.writer, "{}", attributes.join(","))?;
+
+        writeln!(self.writer, "{}", variant_stream.uri)
+    }
+
+    fn write_decryption_key(&mut self, tag: &str, key: &DecryptionKey) -> io::Result<()> {
+        write!(self.writer, "{tag}:")?;
+        let mut attributes = vec![format!("METHOD={}", key.method.as_str())];
+        if let Some(uri) = &key.uri {
+            attributes.push(format!("URI=\"{uri}\""));
+        }
+        if let Some(iv) = &key.iv {
+            let hex = iv.iter().map(|b| format!("{b:02x}")).collect::<String>();
+            attributes.push(format!("IV=0x{hex}"));
+        }
+        if let Some(key_format) = &key.key_format {
+            attributes.push(format!("KEYFORMAT=\"{key_format}\""));
+        }
+        if let Some(key_format_versions) = &key.key_format_versions {
+            let versions = key_format_versions
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            attributes.push(format!("KEYFORMATVERSIONS=\"{versions}\""));
+        }
+        writeln!(self.writer, "{}", attributes.join(","))
+    }
+
+    fn write_initialization_segment(&mut self, map: &InitializationSegment) -> io::Result<()> {
+        write!(self.writer, "{}:URI=\"{}\"", directives::EXT_X_MAP, map.uri)?;
+        if let Some(byte_range) = &map.byte_range {
+            write!(
+                self.writer,
+                ",BYTERANGE=\"{}\"",
+                format_byte_range_literal(byte_range)
+            )?;
+        }
+        writeln!(self.writer)
+    }
+
+    fn write_media(&mut self, media: &M3uMedia) -> io::Result<()> {
+        if let Some(byte_range) = &media.byte_range {
+            writeln!(
+                self.writer,
+                "{}:{}",
+                directives::EXT_X_BYTERANGE,
+                format_byte_range_literal(byte_range)
+            )?;
+        }
+
+        write!(
+            self.writer,
+            "{}:{}",
+            directives::EXTINF,
+            self.format_duration(media.duration)
+        )?;
+        for (key, value) in &media.attributes {
+            write!(self.writer, " {key}=\"{value}\"")?;
+        }
+        writeln!(self.writer, ",{}", media.name.as_deref().unwrap_or_default())?;
+
+        for (key, value) in &media.extension_data {
+            match value {
+                Some(value) => writeln!(self.writer, "{key}:{value}")?,
+                None => writeln!(self.writer, "{key}")?,
+            }
+        }
+
+        writeln!(self.writer, "{}", media.location)
+    }
+
+    fn format_duration(&self, duration: f64) -> String {
+        match self.duration_format {
+            DurationFormat::Integer => format!("{}", duration.trunc() as i64),
+            DurationFormat::Fixed(precision) => format!("{:.*}", precision as usize, duration),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::Parser;
+
+    use super::{DurationFormat, Writer};
+
+    fn round_trip(data: &str, duration_format: DurationFormat) -> String {
+        let mut parser = Parser::new(Cursor::new(data));
+        parser.parse().unwrap();
+        let playlist = parser.get_playlist();
+
+        let mut out = Vec::new();
+        Writer::with_duration_format(&mut out, duration_format)
+            .write_playlist(&playlist)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_fixed_duration() {
+        let data = r"
+#EXTM3U
+#EXT-X-TARGETDURATION:6
+#EXTINF:6,
+21-35-08882.html
+#EXTINF:6,
+21-35-08883.html";
+        let written = round_trip(data, DurationFormat::Fixed(6));
+
+        assert!(written.starts_with("#EXTM3U"));
+        assert!(written.contains("#EXTINF:6.000000,"));
+        assert!(written.contains("21-35-08882.html"));
+        assert!(written.contains("21-35-08883.html"));
+
+        let mut reparsed = Parser::new(Cursor::new(written));
+        reparsed.parse().unwrap();
+        let playlist = reparsed.get_playlist();
+        assert_eq!(playlist.medias.len(), 2);
+        assert_eq!(playlist.medias[0].duration, 6.0);
+    }
+
+    #[test]
+    fn test_round_trip_master_playlist() {
+        let data = r#"
+#EXTM3U
+#EXT-X-SESSION-KEY:METHOD=AES-128,URI="https://example.com/key.bin"
+#EXT-X-STREAM-INF:BANDWIDTH=1280000,RESOLUTION=640x360,CODECS="avc1.4d401e,mp4a.40.2"
+low/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2560000,RESOLUTION=1280x720
+high/index.m3u8"#;
+        let written = round_trip(data, DurationFormat::default());
+
+        let mut reparsed = Parser::new(Cursor::new(written));
+        reparsed.parse().unwrap();
+        let playlist = reparsed.get_playlist();
+
+        assert_eq!(playlist.variant_streams.len(), 2);
+        assert_eq!(playlist.variant_streams[0].uri, "low/index.m3u8");
+        assert_eq!(playlist.variant_streams[0].bandwidth, Some(1_280_000));
+        assert_eq!(playlist.variant_streams[0].resolution, Some((640, 360)));
+        assert_eq!(
+            playlist.variant_streams[0].codecs.as_deref(),
+            Some("avc1.4d401e,mp4a.40.2")
+        );
+        assert_eq!(playlist.variant_streams[1].uri, "high/index.m3u8");
+
+        assert_eq!(playlist.session_keys.len(), 1);
+        assert_eq!(
+            playlist.session_keys[0].uri.as_deref(),
+            Some("https://example.com/key.bin")
+        );
+    }
+
+    #[test]
+    fn test_round_trip_segment_key() {
+        use crate::KeyMethod;
+
+        let data = r#"
+#EXTM3U
+#EXT-X-KEY:METHOD=AES-128,URI="https://example.com/key.bin",IV=0x9c7db8778570d05c3177c349fd9236aa
+#EXTINF:6,
+seg0.ts
+#EXTINF:6,
+seg1.ts
+#EXT-X-KEY:METHOD=NONE
+#EXTINF:6,
+seg2.ts"#;
+        let written = round_trip(data, DurationFormat::default());
+
+        // the key is only re-emitted when it changes, not once per segment.
+        assert_eq!(written.matches("#EXT-X-KEY").count(), 2);
+
+        let mut reparsed = Parser::new(Cursor::new(written));
+        reparsed.parse().unwrap();
+        let playlist = reparsed.get_playlist();
+
+        let key0 = playlist.medias[0].key.as_ref().unwrap();
+        assert_eq!(key0.method, KeyMethod::Aes128);
+        assert_eq!(key0.uri.as_deref(), Some("https://example.com/key.bin"));
+        assert_eq!(playlist.medias[1].key, playlist.medias[0].key);
+        assert_eq!(
+            playlist.medias[2].key.as_ref().unwrap().method,
+            KeyMethod::None
+        );
+    }
+
+    #[test]
+    fn test_round_trip_playlist_control_tags() {
+        use crate::PlaylistType;
+
+        let data = r"
+#EXTM3U
+#EXT-X-VERSION:6
+#EXT-X-TARGETDURATION:6
+#EXT-X-MEDIA-SEQUENCE:8885
+#EXT-X-DISCONTINUITY-SEQUENCE:1
+#EXT-X-PLAYLIST-TYPE:VOD
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXTINF:6,
+seg0.ts
+#EXT-X-ENDLIST";
+        let written = round_trip(data, DurationFormat::default());
+
+        let mut reparsed = Parser::new(Cursor::new(written));
+        reparsed.parse().unwrap();
+        let playlist = reparsed.get_playlist();
+
+        assert_eq!(playlist.version, Some(6));
+        assert_eq!(playlist.target_duration, Some(6));
+        assert_eq!(playlist.media_sequence, Some(8885));
+        assert_eq!(playlist.discontinuity_sequence, Some(1));
+        assert_eq!(playlist.playlist_type, Some(PlaylistType::Vod));
+        assert!(playlist.independent_segments);
+        assert!(playlist.end_list);
+    }
+
+    #[test]
+    fn test_round_trip_map_and_byte_range() {
+        let data = r#"
+#EXTM3U
+#EXT-X-MAP:URI="init.mp4",BYTERANGE="512@0"
+#EXT-X-BYTERANGE:1000@0
+#EXTINF:6,
+fmp4.mp4
+#EXT-X-BYTERANGE:500@1000
+#EXTINF:6,
+fmp4.mp4"#;
+        let written = round_trip(data, DurationFormat::default());
+
+        // the init segment is only re-emitted when it changes.
+        assert_eq!(written.matches("#EXT-X-MAP").count(), 1);
+
+        let mut reparsed = Parser::new(Cursor::new(written));
+        reparsed.parse().unwrap();
+        let playlist = reparsed.get_playlist();
+
+        for media in &playlist.medias {
+            let map = media.map.as_ref().unwrap();
+            assert_eq!(map.uri, "init.mp4");
+            assert_eq!(
+                map.byte_range,
+                Some(crate::ByteRange {
+                    length: 512,
+                    offset: Some(0)
+                })
+            );
+        }
+        assert_eq!(
+            playlist.medias[0].byte_range,
+            Some(crate::ByteRange {
+                length: 1000,
+                offset: Some(0)
+            })
+        );
+        assert_eq!(
+            playlist.medias[1].byte_range,
+            Some(crate::ByteRange {
+                length: 500,
+                offset: Some(1000)
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip_integer_duration() {
+        let data = r#"
+#EXTM3U x-tvg-url="test"
+#EXTINF:1 tvg-id="a",A
+http://example.com/A.m3u8"#;
+        let written = round_trip(data, DurationFormat::Integer);
+
+        assert!(written.contains("#EXTINF:1 "));
+        assert!(!written.contains("#EXTINF:1.000000"));
+
+        let mut reparsed = Parser::new(Cursor::new(written));
+        reparsed.parse().unwrap();
+        let playlist = reparsed.get_playlist();
+        assert_eq!(playlist.attributes.get("x-tvg-url").unwrap(), "test");
+        assert_eq!(playlist.medias[0].name.as_deref(), Some("A"));
+    }
+}